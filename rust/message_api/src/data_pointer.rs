@@ -62,10 +62,9 @@ impl DataPointer {
             if (self.local_pointer.offset + self.local_pointer.len) as usize > message_packet.len {
                 return &[];
             }
-            unsafe {
-                // return core::slice::from_raw_parts(message_packet.data.offset(self.local_pointer.offset as isize), self.local_pointer.len as usize);
-                todo!()
-            }
+            return unsafe {
+                core::slice::from_raw_parts(message_packet.data.offset(self.local_pointer.offset as isize), self.local_pointer.len as usize)
+            };
         }
         unsafe {
             core::slice::from_raw_parts(self.native_pointer.pointer as *const u8, self.native_pointer.len as usize)
@@ -81,8 +80,35 @@ impl DataPointer {
             self.native_pointer.capacity = len;
         }
     }
+
+    /// Copies `data` into the caller-provided buffer this pointer already
+    /// references and sets the reported length to `data.len()`, failing with
+    /// [`Overflow`] if that would exceed `capacity`.
+    /// This lets a callee write its result into a buffer the caller lent it
+    /// up front, without a second allocation, as for a mutable, borrowed
+    /// out-pointer.
+    ///
+    /// Not yet called from `ExecContext::invoke`: wiring it into the
+    /// writeback/rollback path needs the borrowed-mutable-vs-owned mode flag
+    /// on `MessageHeader`, which this crate's checked-in sources don't define
+    /// (see the `BLOCKED (chunk0-5, partial)` note in `hypervisor::lib`).
+    pub unsafe fn set_result(&mut self, data: &[u8]) -> Result<(), Overflow> {
+        if data.len() as u32 > self.native_pointer.capacity {
+            return Err(Overflow);
+        }
+        unsafe {
+            core::ptr::copy_nonoverlapping(data.as_ptr(), self.native_pointer.pointer as *mut u8, data.len());
+        }
+        self.native_pointer.len = data.len() as u32;
+        Ok(())
+    }
 }
 
+/// Returned by [`DataPointer::set_result`] when the data to write is larger
+/// than the pointer's lent `capacity`.
+#[derive(Debug)]
+pub struct Overflow;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +117,30 @@ mod tests {
     fn test_data_pointer_default_size() {
         assert_eq!(core::mem::size_of::<DataPointer>(), 16);
     }
+
+    #[test]
+    fn test_set_result_writes_within_capacity() {
+        let mut buf = [0u8; 4];
+        let mut ptr = DataPointer::default();
+        unsafe {
+            ptr.native_pointer.pointer = buf.as_mut_ptr() as *const ();
+            ptr.native_pointer.capacity = buf.len() as u32;
+
+            ptr.set_result(&[1, 2, 3]).unwrap();
+            assert_eq!(ptr.native_pointer.len, 3);
+        }
+        assert_eq!(&buf, &[1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_set_result_rejects_overflow() {
+        let mut buf = [0u8; 2];
+        let mut ptr = DataPointer::default();
+        unsafe {
+            ptr.native_pointer.pointer = buf.as_mut_ptr() as *const ();
+            ptr.native_pointer.capacity = buf.len() as u32;
+
+            assert!(ptr.set_result(&[1, 2, 3]).is_err());
+        }
+    }
 }
\ No newline at end of file
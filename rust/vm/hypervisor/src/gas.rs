@@ -0,0 +1,103 @@
+//! Deterministic gas metering shared across nested execution frames.
+
+/// A deterministic gas/resource meter. The same meter is shared by every
+/// frame of a call stack so that a child call draws from its caller's
+/// budget rather than getting one of its own.
+pub struct GasMeter {
+    limit: u64,
+    consumed: u64,
+}
+
+impl GasMeter {
+    /// Creates a new gas meter with the given limit.
+    pub fn new(limit: u64) -> Self {
+        Self { limit, consumed: 0 }
+    }
+
+    /// Charges the given amount of gas, failing with [`OutOfGas`] if doing so
+    /// would exceed the meter's limit. When a charge fails, the meter's
+    /// consumed amount is set to its limit, so no gas remains.
+    pub fn charge(&mut self, amount: u64) -> Result<(), OutOfGas> {
+        let consumed = self.consumed.saturating_add(amount);
+        if consumed > self.limit {
+            self.consumed = self.limit;
+            return Err(OutOfGas);
+        }
+        self.consumed = consumed;
+        Ok(())
+    }
+
+    /// Returns the amount of gas remaining before the limit is reached.
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.consumed
+    }
+
+    /// Returns the amount of gas consumed so far.
+    pub fn consumed(&self) -> u64 {
+        self.consumed
+    }
+}
+
+/// Returned by [`GasMeter::charge`] when a charge would exceed the meter's limit.
+#[derive(Debug)]
+pub struct OutOfGas;
+
+/// Base cost charged for each dispatch through `HostBackend::invoke`, system
+/// messages included.
+pub const BASE_INVOKE_COST: u64 = 10;
+
+/// Base cost charged for each key-value store operation, before the per-byte charge.
+pub const BASE_KV_COST: u64 = 10;
+
+/// Cost charged per byte of key and value data touched by a key-value store operation.
+pub const PER_BYTE_KV_COST: u64 = 1;
+
+/// Cost charged per byte allocated via `ExecContext::alloc`.
+pub const PER_BYTE_ALLOC_COST: u64 = 1;
+
+/// Computes the cost of a key-value store operation touching `key_len` bytes
+/// of key and `value_len` bytes of value.
+pub fn kv_op_cost(key_len: usize, value_len: usize) -> u64 {
+    BASE_KV_COST + PER_BYTE_KV_COST * (key_len + value_len) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charge_up_to_limit_succeeds() {
+        let mut meter = GasMeter::new(10);
+        assert!(meter.charge(4).is_ok());
+        assert!(meter.charge(6).is_ok());
+        assert_eq!(meter.consumed(), 10);
+        assert_eq!(meter.remaining(), 0);
+    }
+
+    #[test]
+    fn test_charge_beyond_limit_fails_and_clamps_consumed() {
+        let mut meter = GasMeter::new(10);
+        assert!(meter.charge(4).is_ok());
+        assert!(meter.charge(100).is_err());
+        assert_eq!(meter.consumed(), 10);
+        assert_eq!(meter.remaining(), 0);
+    }
+
+    #[test]
+    fn test_remaining_and_consumed_after_mixed_charges() {
+        let mut meter = GasMeter::new(10);
+        assert!(meter.charge(3).is_ok());
+        assert_eq!(meter.remaining(), 7);
+        assert!(meter.charge(100).is_err());
+        assert_eq!(meter.consumed(), 10);
+        assert_eq!(meter.remaining(), 0);
+        // the meter is exhausted, so even a tiny charge now fails
+        assert!(meter.charge(1).is_err());
+    }
+
+    #[test]
+    fn test_kv_op_cost_scales_with_key_and_value_len() {
+        assert_eq!(kv_op_cost(0, 0), BASE_KV_COST);
+        assert_eq!(kv_op_cost(3, 5), BASE_KV_COST + PER_BYTE_KV_COST * 8);
+    }
+}
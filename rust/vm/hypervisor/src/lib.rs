@@ -1,9 +1,19 @@
 //! Rust Cosmos SDK RFC 003 hypervisor implementation.
-use std::alloc::Layout;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::ops::DerefMut;
-use std::sync::Arc;
+#![no_std]
+
+extern crate alloc;
+
+mod gas;
+
+use core::alloc::Layout;
+use core::cell::RefCell;
+use core::ops::DerefMut;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 use ixc_message_api::AccountID;
 use ixc_message_api::code::{ErrorCode, SystemErrorCode};
 use ixc_message_api::handler::{AllocError, RawHandler, HostBackend, HandlerErrorCode};
@@ -11,25 +21,41 @@ use ixc_message_api::packet::MessagePacket;
 use ixc_vm_api::{HandlerID, VM};
 use ixc_core_macros::message_selector;
 use ixc_message_api::header::MessageHeader;
+use gas::{GasMeter, kv_op_cost, BASE_INVOKE_COST, PER_BYTE_ALLOC_COST};
+
+/// A pluggable allocator for memory requested by handlers during execution.
+/// Injected into the hypervisor so that execution never touches the global
+/// system allocator -- e.g. implementations may hand out memory from a bump
+/// arena the host manages, which is required on targets where a global
+/// allocator isn't available or isn't deterministic.
+pub trait ExecAllocator {
+    /// Allocates memory with the given layout.
+    unsafe fn alloc(&self, layout: Layout) -> Result<*mut u8, AllocError>;
+}
 
 /// Rust Cosmos SDK RFC 003 hypervisor implementation.
-pub struct Hypervisor<ST: StateHandler> {
+pub struct Hypervisor<ST: StateHandler, AL: ExecAllocator, AU: Authorizer> {
     vmdata: Arc<VMData>,
     state_handler: ST,
+    allocator: Arc<AL>,
+    authorizer: Arc<AU>,
 }
 
 struct VMData {
-    vms: HashMap<String, Box<dyn VM>>,
+    vms: BTreeMap<String, Box<dyn VM>>,
 }
 
-impl<ST: StateHandler> Hypervisor<ST> {
-    /// Create a new hypervisor with the given state handler.
-    pub fn new(state_handler: ST) -> Self {
+impl<ST: StateHandler, AL: ExecAllocator, AU: Authorizer> Hypervisor<ST, AL, AU> {
+    /// Create a new hypervisor with the given state handler, allocator and
+    /// authorization policy.
+    pub fn new(state_handler: ST, allocator: AL, authorizer: AU) -> Self {
         Self {
             vmdata: Arc::from(VMData {
-                vms: HashMap::new(),
+                vms: BTreeMap::new(),
             }),
             state_handler,
+            allocator: Arc::new(allocator),
+            authorizer: Arc::new(authorizer),
         }
     }
 
@@ -40,19 +66,15 @@ impl<ST: StateHandler> Hypervisor<ST> {
         Ok(())
     }
 
-    /// Invoke a message packet.
-    pub fn invoke(&self, message_packet: &mut MessagePacket) -> Result<(), ErrorCode> {
-        let mut tx = self.state_handler.new_transaction();
-        tx.push_frame(message_packet.header().sender_account, true).map_err(
-            |e| match e {
-                PushFrameError::VolatileAccessError => ErrorCode::RuntimeSystemError(SystemErrorCode::InvalidHandler),
-            }
-        )?;
-        let mut exec_context = ExecContext {
-            vmdata: self.vmdata.clone(),
-            tx: RefCell::new(tx),
+    /// Invoke a message packet with the given initial gas limit, returning
+    /// the invocation result together with the amount of gas consumed.
+    pub fn invoke(&self, message_packet: &mut MessagePacket, gas_limit: u64) -> (Result<(), ErrorCode>, u64) {
+        let exec_context = match self.new_exec_context(message_packet, gas_limit) {
+            Ok(exec_context) => exec_context,
+            Err(e) => return (Err(e), 0),
         };
         let res = exec_context.invoke(message_packet);
+        let gas_consumed = exec_context.gas.borrow().consumed();
         let tx = exec_context.tx.into_inner();
         if res.is_ok() {
             self.state_handler.commit(tx);
@@ -60,10 +82,59 @@ impl<ST: StateHandler> Hypervisor<ST> {
             tx.rollback();
         }
 
-        res
+        (res, gas_consumed)
+    }
+
+    /// Runs a message packet through the full invocation path, always rolling
+    /// back its effects at the end, so callers can preview a call's outcome
+    /// (success or failure, gas cost and emitted log lines) without
+    /// committing any state.
+    pub fn simulate(&self, message_packet: &mut MessagePacket, gas_limit: u64) -> SimulationResult {
+        let exec_context = match self.new_exec_context(message_packet, gas_limit) {
+            Ok(exec_context) => exec_context,
+            Err(e) => return SimulationResult { error: Some(e), gas_consumed: 0, logs: Vec::new() },
+        };
+        let res = exec_context.invoke(message_packet);
+        let gas_consumed = exec_context.gas.borrow().consumed();
+        let logs = exec_context.log.into_inner();
+        let tx = exec_context.tx.into_inner();
+        tx.rollback();
+
+        SimulationResult {
+            error: res.err(),
+            gas_consumed,
+            logs,
+        }
+    }
+
+    fn new_exec_context(&self, message_packet: &MessagePacket, gas_limit: u64) -> Result<ExecContext<ST::Tx, AL, AU>, ErrorCode> {
+        let mut tx = self.state_handler.new_transaction();
+        tx.push_frame(message_packet.header().sender_account, true).map_err(
+            |e| match e {
+                PushFrameError::VolatileAccessError => ErrorCode::RuntimeSystemError(SystemErrorCode::InvalidHandler),
+            }
+        )?;
+        Ok(ExecContext {
+            vmdata: self.vmdata.clone(),
+            tx: RefCell::new(tx),
+            gas: RefCell::new(GasMeter::new(gas_limit)),
+            log: RefCell::new(Vec::new()),
+            allocator: self.allocator.clone(),
+            authorizer: self.authorizer.clone(),
+        })
     }
 }
 
+/// The outcome of simulating a message without committing its effects.
+pub struct SimulationResult {
+    /// The error code returned by the invocation, if it failed.
+    pub error: Option<ErrorCode>,
+    /// The amount of gas consumed during the simulated invocation.
+    pub gas_consumed: u64,
+    /// Log lines emitted via `ixc.account.v1.log` during the simulated invocation.
+    pub logs: Vec<String>,
+}
+
 /// The state handler traits the hypervisor expects.
 pub trait StateHandler {
     /// The transaction type.
@@ -117,26 +188,66 @@ pub trait KVStore {
     fn delete(&mut self, key: &[u8]);
 }
 
-struct ExecContext<TX: Transaction> {
+/// A pluggable authorization policy consulted before every cross-account
+/// dispatch, so deployments can enforce capability grants, per-selector
+/// ACLs, and delegated authority without baking rules into handlers.
+pub trait Authorizer {
+    /// Decides whether `sender` may invoke `message_selector` on `target`.
+    /// Implementations can read and write their own grant state through
+    /// `manager_state`, so grants can be persisted and revoked.
+    fn authorize(&self, manager_state: &mut dyn KVStore, sender: AccountID, target: AccountID, message_selector: u64) -> AuthDecision;
+}
+
+/// The outcome of consulting an [`Authorizer`] before a cross-account dispatch.
+#[non_exhaustive]
+pub enum AuthDecision {
+    /// The call is allowed to proceed with `sender` as the caller.
+    Allow,
+    /// The call is allowed to proceed, but the callee should see the given
+    /// account as its caller instead of the original sender (delegated authority).
+    Elevate(AccountID),
+    /// The call is denied.
+    Deny,
+}
+
+struct ExecContext<TX: Transaction, AL: ExecAllocator, AU: Authorizer> {
     vmdata: Arc<VMData>,
     tx: RefCell<TX>,
+    gas: RefCell<GasMeter>,
+    log: RefCell<Vec<String>>,
+    allocator: Arc<AL>,
+    authorizer: Arc<AU>,
 }
 
-impl<'a, TX: Transaction> ExecContext<TX> {
-    fn get_account_handler_id(&self, tx: &mut TX, account_id: AccountID) -> Option<HandlerID> {
+impl<TX: Transaction, AL: ExecAllocator, AU: Authorizer> ExecContext<TX, AL, AU> {
+    /// Charges the given amount of gas against the shared meter, mapping
+    /// exhaustion to the error code the rollback path expects.
+    fn charge_gas(&self, amount: u64) -> Result<(), ErrorCode> {
+        self.gas.try_borrow_mut()
+            .map_err(|_| ErrorCode::RuntimeSystemError(SystemErrorCode::FatalExecutionError))?
+            .charge(amount)
+            .map_err(|_| ErrorCode::RuntimeSystemError(SystemErrorCode::OutOfGas))
+    }
+
+    fn get_account_handler_id(&self, tx: &mut TX, account_id: AccountID) -> Result<Option<HandlerID>, ErrorCode> {
         let kv_store = tx.manager_state();
         let key = format!("h:{}", account_id.get());
-        let value = kv_store.get(key.as_bytes())?;
-        parse_handler_id(&value)
+        let value = kv_store.get(key.as_bytes());
+        self.charge_gas(kv_op_cost(key.len(), value.as_ref().map_or(0, Vec::len)))?;
+        Ok(value.and_then(|v| parse_handler_id(&v)))
     }
 
-    fn next_account_id(&self, tx: &mut TX) -> AccountID {
+    fn next_account_id(&self, tx: &mut TX) -> Result<AccountID, ErrorCode> {
         let kv_store = tx.manager_state();
-        let id = kv_store.get(b"next_account_id").map_or(ACCOUNT_ID_NON_RESERVED_START, |v| {
+        let existing = kv_store.get(b"next_account_id");
+        self.charge_gas(kv_op_cost(b"next_account_id".len(), existing.as_ref().map_or(0, Vec::len)))?;
+        let id = existing.map_or(ACCOUNT_ID_NON_RESERVED_START, |v| {
             u64::from_le_bytes(v.try_into().unwrap())
         });
-        kv_store.set(b"next_account_id", &(id + 1).to_le_bytes());
-        AccountID::new(id)
+        let next = (id + 1).to_le_bytes();
+        kv_store.set(b"next_account_id", &next);
+        self.charge_gas(kv_op_cost(b"next_account_id".len(), next.len()))?;
+        Ok(AccountID::new(id))
     }
 }
 
@@ -152,8 +263,11 @@ fn parse_handler_id(value: &[u8]) -> Option<HandlerID> {
     })
 }
 
-impl<TX: Transaction> HostBackend for ExecContext<TX> {
+impl<TX: Transaction, AL: ExecAllocator, AU: Authorizer> HostBackend for ExecContext<TX, AL, AU> {
     fn invoke(&self, message_packet: &mut MessagePacket) -> Result<(), ErrorCode> {
+        // charge a fixed base cost for every dispatch, system or not
+        self.charge_gas(BASE_INVOKE_COST)?;
+
         // get the mutable transaction from the RefCell
         let mut tx = self.tx.try_borrow_mut()
             .map_err(|_| ErrorCode::RuntimeSystemError(SystemErrorCode::FatalExecutionError))?;
@@ -164,16 +278,29 @@ impl<TX: Transaction> HostBackend for ExecContext<TX> {
         if message_packet.header().sender_account != account {
             return Err(ErrorCode::RuntimeSystemError(SystemErrorCode::UnauthorizedCallerAccess));
         }
-        // TODO support authorization middleware
 
         let target_account = message_packet.header().account;
-        // check if the target account is a system account
+        // check if the target account is a system account; system messages
+        // are internal to the hypervisor and aren't subject to the
+        // cross-account authorization policy
         if target_account.is_null() {
             return self.handle_system_message(&mut tx, message_packet);
         }
 
+        // consult the authorization policy before dispatching to another account
+        let message_selector = message_packet.header().message_selector;
+        match self.authorizer.authorize(tx.manager_state(), account, target_account, message_selector) {
+            AuthDecision::Allow => {}
+            AuthDecision::Elevate(elevated) => {
+                message_packet.header_mut().sender_account = elevated;
+            }
+            AuthDecision::Deny => {
+                return Err(ErrorCode::RuntimeSystemError(SystemErrorCode::UnauthorizedCallerAccess));
+            }
+        }
+
         // find the account's handler ID and retrieve its VM
-        let handler_id = self.get_account_handler_id(&mut tx, target_account).
+        let handler_id = self.get_account_handler_id(&mut tx, target_account)?.
             ok_or(ErrorCode::RuntimeSystemError(SystemErrorCode::HandlerNotFound))?;
         let vm = self.vmdata.vms.get(&handler_id.vm).
             ok_or(ErrorCode::RuntimeSystemError(SystemErrorCode::HandlerNotFound))?;
@@ -186,16 +313,33 @@ impl<TX: Transaction> HostBackend for ExecContext<TX> {
         // pop the execution frame
         tx.pop_frame(res.is_ok()).
             map_err(|_| ErrorCode::RuntimeSystemError(SystemErrorCode::InvalidHandler))?;
+        // BLOCKED (chunk0-5, partial): a handler holding a borrowed-mutable
+        // out-pointer is meant to write its result via `DataPointer::set_result`
+        // and have `Overflow` roll this frame back, with `MessageHeader` carrying
+        // a flag that distinguishes a borrowed-mutable out-pointer from an owned
+        // one. Wiring that in here needs `MessageHeader`'s definition and field
+        // layout, which live in `ixc_message_api::header` -- a module this
+        // crate's checked-in sources have never included, not even at the
+        // baseline commit this series started from. Only the callee-side
+        // primitive (`DataPointer::set_result`/`Overflow` in `data_pointer.rs`)
+        // ships from this request; the writeback/rollback wiring into `invoke`
+        // does not, and this request should be tracked as partial, not done.
 
         res
     }
 
     unsafe fn alloc(&self, layout: Layout) -> Result<*mut u8, AllocError> {
-        Ok(std::alloc::alloc(layout))
+        self.charge_gas(PER_BYTE_ALLOC_COST.saturating_mul(layout.size() as u64))
+            .map_err(|_| AllocError)?;
+        unsafe { self.allocator.alloc(layout) }
+    }
+
+    fn gas_left(&self) -> u64 {
+        self.gas.borrow().remaining()
     }
 }
 
-impl<TX: Transaction> ExecContext<TX> {
+impl<TX: Transaction, AL: ExecAllocator, AU: Authorizer> ExecContext<TX, AL, AU> {
     fn handle_system_message(&self, tx: &mut TX, message_packet: &mut MessagePacket) -> Result<(), ErrorCode> {
         match message_packet.header().message_selector {
             CREATE_SELECTOR => unsafe {
@@ -212,7 +356,7 @@ impl<TX: Transaction> ExecContext<TX> {
                     ok_or(ErrorCode::RuntimeSystemError(SystemErrorCode::HandlerNotFound))?;
 
                 // get the next account ID and initialize the account storage
-                let id = self.next_account_id(tx);
+                let id = self.next_account_id(tx)?;
                 let storage_params = desc.storage_params.unwrap_or_default();
                 tx.init_account_storage(id, &storage_params);
 
@@ -240,6 +384,13 @@ impl<TX: Transaction> ExecContext<TX> {
                     _ => res
                 }
             },
+            LOG_SELECTOR => unsafe {
+                let line = message_packet.header().in_pointer1.get(message_packet);
+                if let Ok(line) = core::str::from_utf8(line) {
+                    self.log.borrow_mut().push(line.to_string());
+                }
+                Ok(())
+            },
             _ => {
                 Err(ErrorCode::RuntimeSystemError(SystemErrorCode::HandlerNotFound))
             }
@@ -249,6 +400,7 @@ impl<TX: Transaction> ExecContext<TX> {
 
 const CREATE_SELECTOR: u64 = message_selector!("ixc.account.v1.create");
 const ON_CREATE_SELECTOR: u64 = message_selector!("ixc.account.v1.on_create");
+const LOG_SELECTOR: u64 = message_selector!("ixc.account.v1.log");
 
 
 #[cfg(test)]